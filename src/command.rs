@@ -0,0 +1,148 @@
+/*
+ * This module builds the raw byte buffers to write to
+ * `SWITCHBOT_CHAR_UUID_WRITE` in order to control a SwitchBot device, and
+ * parses the acknowledgement notified back on `SWITCHBOT_CHAR_UUID_NOTIFY`.
+ *
+ * SwitchBot command packets share a fixed shape: a `0x57` magic byte,
+ * followed by a command/action group byte, followed by payload bytes whose
+ * meaning depends on the device model. `Command::encode` produces that
+ * buffer; the free functions below (`bot_press`, `curtain_set_position`,
+ * etc.) are convenience wrappers around the `Command` variants most callers
+ * will reach for.
+ *
+ * Unlike `protocol`, which only reads passively broadcast advertisement
+ * data, sending a command requires an active BLE connection. The
+ * recommended flow is:
+ *
+ * 1. Connect to the device.
+ * 2. Discover services and confirm `SWITCHBOT_SERV_UUID_PRIMARY` is present.
+ * 3. Subscribe to notifications on `SWITCHBOT_CHAR_UUID_NOTIFY`.
+ * 4. Write the encoded command bytes to `SWITCHBOT_CHAR_UUID_WRITE`.
+ * 5. Decode the notified acknowledgement with `decode_response`.
+ *
+ * ```text
+ * use switchbot::{command, SWITCHBOT_CHAR_UUID_NOTIFY, SWITCHBOT_CHAR_UUID_WRITE};
+ *
+ * device.connect().await?;
+ * device.discover_services().await?;
+ *
+ * let notify_char = device.characteristic(SWITCHBOT_CHAR_UUID_NOTIFY)?;
+ * device.subscribe(&notify_char).await?;
+ *
+ * let write_char = device.characteristic(SWITCHBOT_CHAR_UUID_WRITE)?;
+ * device.write(&write_char, &command::bot_press().encode()).await?;
+ *
+ * let ack = device.read_notification(&notify_char).await?;
+ * match command::decode_response(&ack) {
+ *     Some(command::CommandResponse::Success) => println!("bot pressed"),
+ *     Some(command::CommandResponse::Failure(code)) => println!("failed: {}", code),
+ *     None => println!("no acknowledgement received"),
+ * }
+ * ```
+ *
+ */
+
+const SWITCHBOT_COMMAND_MAGIC: u8 = 0x57;
+
+#[derive(Copy, Clone, Debug)]
+pub enum HumidifierMode {
+    /// Turn the atomizer off.
+    Off,
+    /// Let the humidifier pick its own target humidity.
+    Auto,
+    /// Run the atomizer at a fixed level, 0-100.
+    Level(u8),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Command {
+    BotPress,
+    BotTurnOn,
+    BotTurnOff,
+    CurtainSetPosition(u8),
+    PlugSetState(bool),
+    HumidifierSetMode(HumidifierMode),
+}
+
+impl Command {
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Command::BotPress => {
+                vec![SWITCHBOT_COMMAND_MAGIC, 0x01, 0x00]
+            }
+            Command::BotTurnOn => {
+                vec![SWITCHBOT_COMMAND_MAGIC, 0x01, 0x01]
+            }
+            Command::BotTurnOff => {
+                vec![SWITCHBOT_COMMAND_MAGIC, 0x01, 0x02]
+            }
+            Command::CurtainSetPosition(percent) => {
+                vec![SWITCHBOT_COMMAND_MAGIC, 0x0F, 0x45, 0x01, 0x05, (*percent).min(100)]
+            }
+            Command::PlugSetState(on) => {
+                vec![SWITCHBOT_COMMAND_MAGIC, 0x0F, 0x50, 0x01, 0x01, if *on { 0x80 } else { 0x00 }]
+            }
+            Command::HumidifierSetMode(mode) => {
+                let payload: u8 = match mode {
+                    HumidifierMode::Off => 0x00,
+                    HumidifierMode::Auto => 0xFF,
+                    HumidifierMode::Level(level) => (*level).min(100),
+                };
+
+                vec![SWITCHBOT_COMMAND_MAGIC, 0x0F, 0x4C, 0x01, payload]
+            }
+        }
+    }
+}
+
+/// Press the Bot's arm once, as if by hand.
+pub fn bot_press() -> Command {
+    Command::BotPress
+}
+
+/// Hold the Bot's arm in the "on" position (for switch mode).
+pub fn bot_turn_on() -> Command {
+    Command::BotTurnOn
+}
+
+/// Hold the Bot's arm in the "off" position (for switch mode).
+pub fn bot_turn_off() -> Command {
+    Command::BotTurnOff
+}
+
+/// Move the Curtain to `percent` open, 0 (fully closed) to 100 (fully open).
+pub fn curtain_set_position(percent: u8) -> Command {
+    Command::CurtainSetPosition(percent)
+}
+
+/// Turn the Plug's relay on or off.
+pub fn plug_set_state(on: bool) -> Command {
+    Command::PlugSetState(on)
+}
+
+/// Set the Humidifier's atomizer mode.
+pub fn humidifier_set_mode(mode: HumidifierMode) -> Command {
+    Command::HumidifierSetMode(mode)
+}
+
+/*
+ * Decode the acknowledgement a SwitchBot device sends back on
+ * `SWITCHBOT_CHAR_UUID_NOTIFY` after a command is written.
+ *
+ * Returns None if `data` is empty, since that's not a packet we can make
+ * sense of rather than a reported failure.
+ *
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CommandResponse {
+    Success,
+    Failure(u8),
+}
+
+pub fn decode_response(data: &[u8]) -> Option<CommandResponse> {
+    match data.first() {
+        Some(0x01) => Some(CommandResponse::Success),
+        Some(code) => Some(CommandResponse::Failure(*code)),
+        None => None,
+    }
+}
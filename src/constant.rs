@@ -0,0 +1,26 @@
+/*
+ * Bluetooth LE identifiers used by SwitchBot devices.
+ *
+ * SwitchBot devices are inconsistent about which Bluetooth SIG manufacturer
+ * ID they advertise under: most models use Woan Technology's own registered
+ * ID, but some (notably the Plug Mini) advertise under Nordic's ID instead,
+ * presumably because they were built around a Nordic reference design.
+ *
+ */
+pub const SWITCHBOT_WOAN_MANUFACTURER_ID: u16 = 0x0969;
+pub const SWITCHBOT_NORDIC_MANUFACTURER_ID: u16 = 0x0059;
+
+/*
+ * GATT service/characteristic UUIDs exposed by SwitchBot devices once
+ * connected. The primary service is what you should check for after
+ * connecting to confirm a device is actually a SwitchBot device, since
+ * passive advertisement parsing alone cannot guarantee that (see the
+ * module comment in protocol.rs).
+ *
+ */
+pub const SWITCHBOT_SERV_UUID_PRIMARY: &str = "cba20d00-224d-11e6-9fb8-0002a5d5c51b";
+pub const SWITCHBOT_SERV_UUID_WOAN_TECHNOLOGY: &str = "0000fd3d-0000-1000-8000-00805f9b34fb";
+pub const SWITCHBOT_SERV_UUID_WOAN_TECHNOLOGY2: &str = "00001800-0000-1000-8000-00805f9b34fb";
+
+pub const SWITCHBOT_CHAR_UUID_WRITE: &str = "cba20002-224d-11e6-9fb8-0002a5d5c51b";
+pub const SWITCHBOT_CHAR_UUID_NOTIFY: &str = "cba20003-224d-11e6-9fb8-0002a5d5c51b";
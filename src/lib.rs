@@ -1,8 +1,10 @@
 pub mod constant;
 pub mod protocol;
 pub mod model;
+pub mod command;
 
-pub use crate::protocol::{decode_model, decode_data};
+pub use crate::protocol::{decode_model, decode_data, try_decode_data, DecodeError, expected_manufacturer_id};
+pub use crate::protocol::{expected_service_data_len, expected_manufacturer_data_len};
 pub use crate::model::{SwitchBotDeviceModel, SwitchBotData};
 
 pub use crate::constant::{SWITCHBOT_WOAN_MANUFACTURER_ID, SWITCHBOT_NORDIC_MANUFACTURER_ID};
@@ -20,7 +22,7 @@ mod tests {
         let manufacturer_data: [u8; 12] = [0; 12];
 
         let (model,
-             switchbot_data) = protocol::decode_data(&service_data, Some(&manufacturer_data));
+             switchbot_data) = protocol::decode_data(&service_data, Some(&manufacturer_data), None);
 
         assert!(model.is_none());
         assert!(switchbot_data.is_none());
@@ -31,7 +33,7 @@ mod tests {
         let service_data: [u8; 3] = [0x48, 0x40, 0x64];
 
         let (model,
-             switchbot_data) = protocol::decode_data(&service_data, None);
+             switchbot_data) = protocol::decode_data(&service_data, None, None);
 
         let Some(SwitchBotDeviceModel::Bot) = model else {
             return Err("invalid model");
@@ -55,7 +57,7 @@ mod tests {
         let service_data: [u8; 3] = [0x48, 0x0, 0x32];
 
         let (model,
-             switchbot_data) = protocol::decode_data(&service_data, None);
+             switchbot_data) = protocol::decode_data(&service_data, None, None);
 
         let Some(SwitchBotDeviceModel::Bot) = model else {
             return Err("invalid model");
@@ -75,16 +77,16 @@ mod tests {
     }
 
     #[test]
-    fn switchbot_model_meterplus_battery_100_temperature_23_humidity_42_test() -> Result<(), &'static str> {
-        let service_data: [u8; 6] = [0x69, 
+    fn switchbot_model_meterplus_battery_100_temperature_23_5_humidity_42_test() -> Result<(), &'static str> {
+        let service_data: [u8; 6] = [0x69,
                                      0x00,  // ignored on this model
                                      0x64,  // 100% battery level
-                                     0x00, 
-                                     0x80,  // positive temperature sign in MSB
-                                     0x2A]; // 42% humidity];
+                                     0x05,  // 0.5 degree tenths
+                                     0x97,  // positive temperature sign in MSB, 23 degrees
+                                     0x2A]; // 42% humidity
 
         let (model,
-             switchbot_data) = protocol::decode_data(&service_data, None);
+             switchbot_data) = protocol::decode_data(&service_data, None, None);
 
         let Some(SwitchBotDeviceModel::MeterPlus) = model else {
             return Err("invalid model");
@@ -92,8 +94,39 @@ mod tests {
 
         match switchbot_data {
             Some(SwitchBotData::Meter { battery, temperature, humidity }) => {
-                assert_eq!(battery, 100);
-                assert_eq!(temperature, 23);
+                assert_eq!(battery, Some(100));
+                assert_eq!(temperature, 23.5);
+                assert_eq!(humidity, 42);
+
+                return Ok(());
+            },
+            _ => {
+                return Err("invalid meter data");
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_model_meter_passive_temperature_23_humidity_42_no_battery_test() -> Result<(), &'static str> {
+        let manufacturer_data: [u8; 8] = [0x69, 0x09,  // Woan manufacturer ID
+                                           0x00,  // ignored on this model
+                                           0x00,  // ignored on this model
+                                           0x00,  // battery isn't broadcast this way
+                                           0x00,
+                                           0x97,  // positive temperature sign in MSB, 23 degrees
+                                           0x2A]; // 42% humidity
+
+        let (model,
+             switchbot_data) = protocol::decode_data(&[], Some(&manufacturer_data), None);
+
+        let Some(SwitchBotDeviceModel::Meter) = model else {
+            return Err("invalid model");
+        };
+
+        match switchbot_data {
+            Some(SwitchBotData::Meter { battery, temperature, humidity }) => {
+                assert_eq!(battery, None);
+                assert_eq!(temperature, 23.0);
                 assert_eq!(humidity, 42);
 
                 return Ok(());
@@ -103,4 +136,270 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn switchbot_model_hub2_passive_temperature_23_humidity_42_light_level_5_test() -> Result<(), &'static str> {
+        let service_data: [u8; 1] = [0x76]; // model byte only, no SCAN_RSP
+
+        let manufacturer_data: [u8; 8] = [0x69, 0x09,  // Woan manufacturer ID
+                                           0x00,
+                                           0x00,
+                                           0x00,
+                                           0x97,  // positive temperature sign in MSB, 23 degrees
+                                           0x2A,  // 42% humidity
+                                           0x05]; // brightness level 5
+
+        let (model,
+             switchbot_data) = protocol::decode_data(&service_data, Some(&manufacturer_data), None);
+
+        let Some(SwitchBotDeviceModel::Hub2) = model else {
+            return Err("invalid model");
+        };
+
+        match switchbot_data {
+            Some(SwitchBotData::Hub { temperature, humidity, light_level }) => {
+                assert_eq!(temperature, 23.0);
+                assert_eq!(humidity, 42);
+                assert_eq!(light_level, 5);
+
+                return Ok(());
+            },
+            _ => {
+                return Err("invalid hub data");
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_model_hub2_temperature_23_humidity_42_light_level_5_test() -> Result<(), &'static str> {
+        let service_data: [u8; 3] = [0x76, 0x00, 0x00];
+
+        let manufacturer_data: [u8; 8] = [0x69, 0x09,  // Woan manufacturer ID
+                                           0x00,
+                                           0x00,
+                                           0x00,
+                                           0x97,  // positive temperature sign in MSB, 23 degrees
+                                           0x2A,  // 42% humidity
+                                           0x05]; // brightness level 5
+
+        let (model,
+             switchbot_data) = protocol::decode_data(&service_data, Some(&manufacturer_data), None);
+
+        let Some(SwitchBotDeviceModel::Hub2) = model else {
+            return Err("invalid model");
+        };
+
+        match switchbot_data {
+            Some(SwitchBotData::Hub { temperature, humidity, light_level }) => {
+                assert_eq!(temperature, 23.0);
+                assert_eq!(humidity, 42);
+                assert_eq!(light_level, 5);
+
+                return Ok(());
+            },
+            _ => {
+                return Err("invalid hub data");
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_try_decode_data_reports_unexpected_service_data_length_test() -> Result<(), &'static str> {
+        let service_data: [u8; 4] = [0x48, 0x40, 0x64, 0x00];
+
+        match protocol::try_decode_data(&service_data, None, None) {
+            Err(protocol::DecodeError::UnexpectedServiceDataLength { model: SwitchBotDeviceModel::Bot, expected: 3, actual: 4 }) => {
+                return Ok(());
+            }
+            other => {
+                return Err(match other {
+                    Ok(_) => "expected an error, got Ok",
+                    _ => "wrong error variant",
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_try_decode_data_reports_unknown_model_test() -> Result<(), &'static str> {
+        let service_data: [u8; 3] = [0xFF, 0x00, 0x00];
+
+        match protocol::try_decode_data(&service_data, None, None) {
+            Err(protocol::DecodeError::UnknownModel(0xFF)) => {
+                return Ok(());
+            }
+            other => {
+                return Err(match other {
+                    Ok(_) => "expected an error, got Ok",
+                    _ => "wrong error variant",
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_try_decode_meter_from_manufacturer_data_rejects_recognized_non_meter_model_test() -> Result<(), &'static str> {
+        let service_data: [u8; 1] = [0x48]; // Bot, not Meter/MeterPlus
+
+        match protocol::try_decode_data(&service_data, None, None) {
+            Err(protocol::DecodeError::UnsupportedModel(SwitchBotDeviceModel::Bot)) => {
+                return Ok(());
+            }
+            other => {
+                return Err(match other {
+                    Ok(_) => "expected an error, got Ok",
+                    _ => "wrong error variant",
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_try_decode_data_rejects_mismatched_manufacturer_id_test() -> Result<(), &'static str> {
+        let service_data: [u8; 3] = [0x48, 0x40, 0x64];
+
+        match protocol::try_decode_data(&service_data, None, Some(SWITCHBOT_NORDIC_MANUFACTURER_ID)) {
+            Err(protocol::DecodeError::UnexpectedManufacturerId {
+                model: SwitchBotDeviceModel::Bot,
+                expected: SWITCHBOT_WOAN_MANUFACTURER_ID,
+                actual: SWITCHBOT_NORDIC_MANUFACTURER_ID,
+            }) => {
+                return Ok(());
+            }
+            other => {
+                return Err(match other {
+                    Ok(_) => "expected an error, got Ok",
+                    _ => "wrong error variant",
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_model_contact_sensor_door_open_test() -> Result<(), &'static str> {
+        let service_data: [u8; 6] = [0x64,
+                                      0b01000000,  // motion detected
+                                      0x32,        // 50% battery
+                                      0b00000010,  // door open, not a button-press timeout
+                                      0b00000001,  // bright
+                                      0x00];
+
+        let (model,
+             switchbot_data) = protocol::decode_data(&service_data, None, None);
+
+        let Some(SwitchBotDeviceModel::ContactSensor) = model else {
+            return Err("invalid model");
+        };
+
+        match switchbot_data {
+            Some(SwitchBotData::Contact { battery, motion_detected, door_open, is_light, last_opened_timeout }) => {
+                assert_eq!(battery, 50);
+                assert!(motion_detected);
+                assert!(door_open);
+                assert!(is_light);
+                assert!(!last_opened_timeout);
+
+                return Ok(());
+            },
+            _ => {
+                return Err("invalid contact sensor data");
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_model_motion_sensor_test() -> Result<(), &'static str> {
+        let service_data: [u8; 5] = [0x73,
+                                      0b01000000,  // motion detected
+                                      0x32,        // 50% battery
+                                      0x00,
+                                      0b00000001]; // bright
+
+        let (model,
+             switchbot_data) = protocol::decode_data(&service_data, None, None);
+
+        let Some(SwitchBotDeviceModel::MotionSensor) = model else {
+            return Err("invalid model");
+        };
+
+        match switchbot_data {
+            Some(SwitchBotData::Motion { battery, motion_detected, is_light }) => {
+                assert_eq!(battery, 50);
+                assert!(motion_detected);
+                assert!(is_light);
+
+                return Ok(());
+            },
+            _ => {
+                return Err("invalid motion sensor data");
+            }
+        }
+    }
+
+    #[test]
+    fn switchbot_data_temperature_fahrenheit_test() {
+        let meter_data = SwitchBotData::Meter { battery: Some(100), temperature: 23.5, humidity: 42 };
+        assert_eq!(meter_data.temperature_fahrenheit(), Some(74.3));
+
+        let bot_data = SwitchBotData::Bot { battery: 100, state: true };
+        assert_eq!(bot_data.temperature_fahrenheit(), None);
+    }
+
+    #[test]
+    fn switchbot_command_bot_press_encode_test() {
+        assert_eq!(command::bot_press().encode(), vec![0x57, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn switchbot_command_bot_turn_on_encode_test() {
+        assert_eq!(command::bot_turn_on().encode(), vec![0x57, 0x01, 0x01]);
+    }
+
+    #[test]
+    fn switchbot_command_bot_turn_off_encode_test() {
+        assert_eq!(command::bot_turn_off().encode(), vec![0x57, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn switchbot_command_curtain_set_position_encode_test() {
+        assert_eq!(command::curtain_set_position(42).encode(), vec![0x57, 0x0F, 0x45, 0x01, 0x05, 0x2A]);
+    }
+
+    #[test]
+    fn switchbot_command_curtain_set_position_clamps_to_100_test() {
+        assert_eq!(command::curtain_set_position(150).encode(), vec![0x57, 0x0F, 0x45, 0x01, 0x05, 100]);
+    }
+
+    #[test]
+    fn switchbot_command_plug_set_state_encode_test() {
+        assert_eq!(command::plug_set_state(true).encode(), vec![0x57, 0x0F, 0x50, 0x01, 0x01, 0x80]);
+        assert_eq!(command::plug_set_state(false).encode(), vec![0x57, 0x0F, 0x50, 0x01, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn switchbot_command_humidifier_set_mode_encode_test() {
+        assert_eq!(command::humidifier_set_mode(command::HumidifierMode::Off).encode(), vec![0x57, 0x0F, 0x4C, 0x01, 0x00]);
+        assert_eq!(command::humidifier_set_mode(command::HumidifierMode::Auto).encode(), vec![0x57, 0x0F, 0x4C, 0x01, 0xFF]);
+        assert_eq!(command::humidifier_set_mode(command::HumidifierMode::Level(60)).encode(), vec![0x57, 0x0F, 0x4C, 0x01, 60]);
+    }
+
+    #[test]
+    fn switchbot_command_humidifier_set_mode_level_clamps_to_100_test() {
+        assert_eq!(command::humidifier_set_mode(command::HumidifierMode::Level(150)).encode(), vec![0x57, 0x0F, 0x4C, 0x01, 100]);
+    }
+
+    #[test]
+    fn switchbot_command_decode_response_success_test() {
+        assert_eq!(command::decode_response(&[0x01]), Some(command::CommandResponse::Success));
+    }
+
+    #[test]
+    fn switchbot_command_decode_response_failure_test() {
+        assert_eq!(command::decode_response(&[0x05]), Some(command::CommandResponse::Failure(0x05)));
+    }
+
+    #[test]
+    fn switchbot_command_decode_response_empty_test() {
+        assert_eq!(command::decode_response(&[]), None);
+    }
 }
@@ -1,9 +1,28 @@
 #[derive(Copy, Clone, Debug)]
 pub enum SwitchBotData {
     Bot { battery: u8,  state: bool },
-    Meter { battery: u8, temperature: i32, humidity: u8 },
+    Meter { battery: Option<u8>, temperature: f32, humidity: u8 },
     Plug { wifi_rssi: i16, state: bool, watts: i16, overload: bool },
     Humidifier { state: bool, humidity: u8, auto_mode: bool },
+    Hub { temperature: f32, humidity: u8, light_level: u8 },
+    Contact { battery: u8, motion_detected: bool, door_open: bool, is_light: bool, last_opened_timeout: bool },
+    Motion { battery: u8, motion_detected: bool, is_light: bool },
+}
+
+impl SwitchBotData {
+    /*
+     * The Celsius-to-Fahrenheit conversion of this reading's temperature, if
+     * it reports one. Returns `None` for variants that don't carry a
+     * temperature reading.
+     *
+     */
+    pub fn temperature_fahrenheit(&self) -> Option<f32> {
+        match self {
+            SwitchBotData::Meter { temperature, .. } => Some(temperature * 9.0 / 5.0 + 32.0),
+            SwitchBotData::Hub { temperature, .. } => Some(temperature * 9.0 / 5.0 + 32.0),
+            _ => None,
+        }
+    }
 }
 
 #[repr(C)]
@@ -31,4 +50,5 @@ pub enum SwitchBotDeviceModel {
     MotionSensor = 0x73,
     MeterAdd = 0x74,
     ColorBulb = 0x75,
+    Hub2 = 0x76,
 }
@@ -1,8 +1,8 @@
-/* 
- * This module parses SwitchBot Bluetooth LE advertisement data. It does not
- * currently support constructing commands to send to SwitchBot devices, but
- * that is a planned feature.
- * 
+/*
+ * This module parses SwitchBot Bluetooth LE advertisement data. Constructing
+ * commands to send to SwitchBot devices is handled separately, by the
+ * `command` module.
+ *
  * To use this code, it is advised that you connect to each BLE device, discover
  * services to look for the SwitchBot primary service UUID, and only attempt
  * to parse the service/manufacturer data once it is confirmed that it is a
@@ -35,6 +35,7 @@
  */
 
 use crate::model::{SwitchBotDeviceModel, SwitchBotData};
+use crate::constant::{SWITCHBOT_WOAN_MANUFACTURER_ID, SWITCHBOT_NORDIC_MANUFACTURER_ID};
 
 /*
  * Parse the first byte of a Bluetooth LE Service Data
@@ -115,129 +116,380 @@ pub fn decode_model(data: u8) -> Option<SwitchBotDeviceModel> {
         0x75 => {
             Some(SwitchBotDeviceModel::ColorBulb)
         }
+        0x76 => {
+            Some(SwitchBotDeviceModel::Hub2)
+        }
         _ => {
             None
         }
     }
 }
 
+/*
+ * Why `try_decode_data` can fail to recognize a packet as a SwitchBot one.
+ *
+ * `ServiceDataTooShort` and `UnknownModel` mean the packet probably isn't a
+ * SwitchBot packet at all (or is one we don't yet recognize). The other
+ * variants mean the model *was* recognized but the payload didn't look like
+ * what that model is supposed to send, which is worth distinguishing since
+ * it can point at a firmware/encoding change rather than a false positive.
+ * `UnsupportedModel` is its own case of that: the model byte matched a real,
+ * known `SwitchBotDeviceModel`, but this crate doesn't decode that model in
+ * the path that was taken (e.g. it's not Meter/MeterPlus/Hub2 in the passive
+ * manufacturer-data fallback, or it's a recognized model with no decode arm
+ * at all yet) — don't confuse it with `UnknownModel`, which means the byte
+ * didn't match any known model in the first place.
+ *
+ */
+#[derive(Copy, Clone, Debug)]
+pub enum DecodeError {
+    ServiceDataTooShort,
+    UnexpectedServiceDataLength { model: SwitchBotDeviceModel, expected: usize, actual: usize },
+    MissingManufacturerData,
+    UnexpectedManufacturerDataLength { model: SwitchBotDeviceModel, expected: usize, actual: usize },
+    UnexpectedManufacturerId { model: SwitchBotDeviceModel, expected: u16, actual: u16 },
+    UnsupportedModel(SwitchBotDeviceModel),
+    UnknownModel(u8),
+}
+
+/*
+ * The manufacturer ID a given SwitchBot model advertises its manufacturer
+ * data under. Most models register under Woan Technology's own ID, but the
+ * Plug Mini variants advertise under Nordic's instead.
+ *
+ * Since the service data's model byte gives an initial (unreliable) guess
+ * at the device model before the manufacturer data is even inspected,
+ * knowing this in advance lets a passive scanner narrow down which
+ * manufacturer ID to expect, tightening that guess without needing to
+ * connect to the device first.
+ *
+ */
+pub fn expected_manufacturer_id(model: SwitchBotDeviceModel) -> u16 {
+    match model {
+        SwitchBotDeviceModel::PlugMiniUS | SwitchBotDeviceModel::PlugMiniJP => SWITCHBOT_NORDIC_MANUFACTURER_ID,
+        _ => SWITCHBOT_WOAN_MANUFACTURER_ID,
+    }
+}
+
+fn check_manufacturer_id(model: SwitchBotDeviceModel, manufacturer_id: Option<u16>) -> Result<(), DecodeError> {
+    let expected = expected_manufacturer_id(model);
+
+    match manufacturer_id {
+        Some(actual) if actual != expected => {
+            Err(DecodeError::UnexpectedManufacturerId { model, expected, actual })
+        }
+        _ => Ok(()),
+    }
+}
+
+/*
+ * The service data length a given SwitchBot model is expected to broadcast
+ * in an active scan's SCAN_RSP, if it relies on service data length to
+ * validate a reading at all. `None` means the model either isn't decoded
+ * yet, or gets its reading from manufacturer data instead (see
+ * `expected_manufacturer_data_len`).
+ *
+ */
+pub fn expected_service_data_len(model: SwitchBotDeviceModel) -> Option<usize> {
+    match model {
+        SwitchBotDeviceModel::Bot => Some(3),
+        SwitchBotDeviceModel::Meter | SwitchBotDeviceModel::MeterPlus => Some(6),
+        SwitchBotDeviceModel::Humidifier => Some(5),
+        SwitchBotDeviceModel::ContactSensor => Some(6),
+        SwitchBotDeviceModel::MotionSensor => Some(5),
+        _ => None,
+    }
+}
+
+/*
+ * The manufacturer data length a given SwitchBot model is expected to
+ * broadcast, if it relies on manufacturer data length to validate a reading
+ * at all. `None` means the model doesn't use manufacturer data for its
+ * reading.
+ *
+ * Meter and MeterPlus only rely on this in the passive (no-SCAN_RSP) case,
+ * since an active scan instead validates their service data length.
+ *
+ */
+pub fn expected_manufacturer_data_len(model: SwitchBotDeviceModel) -> Option<usize> {
+    match model {
+        SwitchBotDeviceModel::PlugMiniUS | SwitchBotDeviceModel::PlugMiniJP => Some(12),
+        SwitchBotDeviceModel::Hub2 => Some(8),
+        SwitchBotDeviceModel::Meter | SwitchBotDeviceModel::MeterPlus => Some(8),
+        _ => None,
+    }
+}
+
+fn check_service_data_len(model: SwitchBotDeviceModel, service_data: &[u8]) -> Result<(), DecodeError> {
+    match expected_service_data_len(model) {
+        Some(expected) if service_data.len() != expected => {
+            Err(DecodeError::UnexpectedServiceDataLength { model, expected, actual: service_data.len() })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_manufacturer_data_len(model: SwitchBotDeviceModel, manufacturer_data: &[u8]) -> Result<(), DecodeError> {
+    match expected_manufacturer_data_len(model) {
+        Some(expected) if manufacturer_data.len() != expected => {
+            Err(DecodeError::UnexpectedManufacturerDataLength { model, expected, actual: manufacturer_data.len() })
+        }
+        _ => Ok(()),
+    }
+}
+
+/*
+ * Decode a SwitchBot sign-magnitude temperature reading: the high bit of
+ * `sign_and_int` is the sign (set means positive), the remaining 7 bits are
+ * the whole-degree Celsius magnitude, and the low nibble of `tenths` is the
+ * fractional tenths of a degree.
+ *
+ */
+fn decode_temperature(sign_and_int: u8, tenths: u8) -> f32 {
+    let sign: f32 = if sign_and_int & 0b10000000 == 0b10000000 { 1.0 } else { -1.0 };
+
+    sign * ((sign_and_int & 0b01111111) as f32 + (tenths & 0b00001111) as f32 / 10.0)
+}
+
 /*
  * Decode BLE advertisment data from a SwitchBot device.
- * 
+ *
  * The location of the data depends on the device model, which is determined
- * by the first byte of the service_data in SCAN_RSP. 
- * 
+ * by the first byte of the service_data in SCAN_RSP.
+ *
  * However, finding a valid device model identifier does NOT guarantee that the
  * device is actually a SwitchBot device; a single byte can only have 256 distinct
  * values, which means there is a significant chance of false positives.
- * 
+ *
  * There is an additional check below to reduce those false positives: the length of
- * the service data or manufacturer data fields. However, that isn't conclusive. 
- * 
+ * the service data or manufacturer data fields. However, that isn't conclusive.
+ *
  * In practice you should connect to the device and determine if it provides the
  * primary SwitchBot service UUID, and only then rely on the values.
  *
+ * This is a thin wrapper around `try_decode_data` for callers that just want
+ * to know "is this a SwitchBot packet", collapsing every failure reason down
+ * to `None`. Prefer `try_decode_data` if you want to tell a malformed payload
+ * apart from a packet that was never a SwitchBot one to begin with.
+ *
  */
 pub fn decode_data(service_data: &[u8],
-                   manufacturer_data: Option<&[u8]>) -> (Option<SwitchBotDeviceModel>, 
-                                                         Option<SwitchBotData>) {
+                   manufacturer_data: Option<&[u8]>,
+                   manufacturer_id: Option<u16>) -> (Option<SwitchBotDeviceModel>,
+                                                     Option<SwitchBotData>) {
+    match try_decode_data(service_data, manufacturer_data, manufacturer_id) {
+        Ok((model, switchbot_data)) => (Some(model), Some(switchbot_data)),
+        Err(_) => (None, None),
+    }
+}
+
+/*
+ * Decode BLE advertisment data from a SwitchBot device, same as
+ * `decode_data`, but returning a `DecodeError` on failure instead of
+ * silently collapsing to `None`.
+ *
+ * `manufacturer_id` is the manufacturer ID the advertisement was reported
+ * under, if known; when present, it's checked against
+ * `expected_manufacturer_id(model)` once the model is identified, to reject
+ * packets a real SwitchBot device of that model wouldn't send. Pass `None`
+ * to skip that check, e.g. when the manufacturer ID wasn't captured.
+ *
+ */
+pub fn try_decode_data(service_data: &[u8],
+                       manufacturer_data: Option<&[u8]>,
+                       manufacturer_id: Option<u16>) -> Result<(SwitchBotDeviceModel, SwitchBotData), DecodeError> {
     /*
      * No SwitchBot device broadcasts a SCAN_RSP packet with a service data
-     * field smaller than 3 bytes. Skipping those packets will reduce the
-     * chances that devices from other manufacturers will look like SwitchBot
-     * devices.
+     * field smaller than 3 bytes in active scan mode. However, when scanned
+     * passively (no SCAN_RSP), Meter, MeterPlus and Hub2 devices report their
+     * reading in the manufacturer data only, with service data empty,
+     * truncated to just the model byte, or absent entirely. Try that
+     * fallback here before giving up, since otherwise we'd reject passive
+     * advertisements from those models outright.
      */
     if service_data.len() < 3 {
-        return (None, None);
+        return try_decode_sensor_from_manufacturer_data(service_data, manufacturer_data, manufacturer_id);
     }
 
     let Some(model) = decode_model(service_data[0]) else {
-        return (None, None);
+        return Err(DecodeError::UnknownModel(service_data[0]));
     };
 
+    check_manufacturer_id(model, manufacturer_id)?;
+    check_service_data_len(model, service_data)?;
+
     match model {
-        SwitchBotDeviceModel::Bot => {            
-            if service_data.len() != 3 {
-                println!("Found SwitchBotDevice::Bot but service data length invalid: {}", service_data.len());
-                return (None, None);
-            }
-            
+        SwitchBotDeviceModel::Bot => {
             let state: bool = if service_data[1] & 0b01000000 == 0b01000000 { true } else { false };
-            
+
             let switchbot_data = SwitchBotData::Bot {
                 battery: service_data[2] & 0b01111111,
                 state: state,
             };
-            
-            return (Some(model), Some(switchbot_data));
-        }
-        SwitchBotDeviceModel::Meter | SwitchBotDeviceModel::MeterPlus => {            
-            if service_data.len() != 6 {
-                println!("Found SwitchBotDevice::Meter but service data length invalid: {}", service_data.len());
-                return (None, None);
-            }
-
-            let temp_sign: i32 = if service_data[4] & 0b10000000 == 0b10000000 { 1 } else  { -1 };
-            let temp_c: i32 = temp_sign * ((service_data[4] & 0b01111111) as i32 + (service_data[3] & 0b00001111) as i32 / 10);
-            
+
+            return Ok((model, switchbot_data));
+        }
+        SwitchBotDeviceModel::Meter | SwitchBotDeviceModel::MeterPlus => {
             let switchbot_data = SwitchBotData::Meter {
-                temperature: temp_c,
+                temperature: decode_temperature(service_data[4], service_data[3]),
                 humidity: service_data[5] & 0b01111111,
-                battery: service_data[2] & 0b01111111,
+                battery: Some(service_data[2] & 0b01111111),
             };
-            
-            return (Some(model), Some(switchbot_data));
+
+            return Ok((model, switchbot_data));
         }
         SwitchBotDeviceModel::Humidifier => {
-            if service_data.len() != 5 {
-                println!("Found SwitchBotDevice::Humidifier but service data length invalid: {}", service_data.len());
-                return (None, None);
-            }
-            
             let state: bool = if service_data[1] & 0b10000000 == 0b10000000 { true } else { false };
             let auto_mode: bool= if service_data[4] & 0b10000000 == 0b10000000 { true } else { false };
             let humidity_setting: u8 = service_data[4] & 0b01111111;
-            
+
             let switchbot_data = SwitchBotData::Humidifier {
                 humidity: humidity_setting,
                 state: state,
                 auto_mode: auto_mode,
             };
-            
-            return (Some(model), Some(switchbot_data));
+
+            return Ok((model, switchbot_data));
         }
-        SwitchBotDeviceModel::PlugMiniUS | SwitchBotDeviceModel::PlugMiniJP => {        
+        SwitchBotDeviceModel::PlugMiniUS | SwitchBotDeviceModel::PlugMiniJP => {
             let Some(manufacturer_data) = manufacturer_data else {
-                return (None, None);
+                return Err(DecodeError::MissingManufacturerData);
             };
 
             /*
              * Number takes into account the fact that the manufacturingData buffer is a map, not raw. The
              * first 2 bytes are part of the manufacturing data ID which the bluez-async API consumes as a
              * HashMap key.
-             * 
+             *
              */
-            if manufacturer_data.len() != 12 {
-                println!("Found SwitchBotDevicePlugMini::US|JP but service data length invalid: {}", manufacturer_data.len());
-                return (None, None);
-            }
-            
+            check_manufacturer_data_len(model, manufacturer_data)?;
+
             let state: bool = if manufacturer_data[7] == 0x80 { true } else { false };
             let overload: bool = (manufacturer_data[10] & 0b10000000) == 0b10000000;
             let watts: i16 = (((manufacturer_data[10] as i16 & 0b01111111) << 8) + manufacturer_data[11] as i16) / 10;
-            
+
             let sensor_data = SwitchBotData::Plug {
                 wifi_rssi: -(manufacturer_data[9] as i16),
                 state: state,
                 watts: watts,
                 overload: overload
             };
-            
-            return (Some(model), Some(sensor_data))
+
+            return Ok((model, sensor_data));
+        }
+        SwitchBotDeviceModel::Hub2 => {
+            let Some(manufacturer_data) = manufacturer_data else {
+                return Err(DecodeError::MissingManufacturerData);
+            };
+
+            /*
+             * Like the Plug Mini, the Hub 2's useful reading (temperature,
+             * humidity, ambient light level) lives entirely in the
+             * manufacturer data rather than the service data, so check its
+             * length here to reduce false positives instead.
+             */
+            check_manufacturer_data_len(model, manufacturer_data)?;
+
+            let switchbot_data = SwitchBotData::Hub {
+                temperature: decode_temperature(manufacturer_data[5], manufacturer_data[4]),
+                humidity: manufacturer_data[6] & 0b01111111,
+                light_level: manufacturer_data[7] & 0b00001111,
+            };
+
+            return Ok((model, switchbot_data));
+        }
+        SwitchBotDeviceModel::ContactSensor => {
+            let motion_detected: bool = service_data[1] & 0b01000000 == 0b01000000;
+            let battery: u8 = service_data[2] & 0b01111111;
+            let door_open: bool = service_data[3] & 0b00000010 == 0b00000010;
+            let last_opened_timeout: bool = service_data[3] & 0b00000001 == 0b00000001;
+            let is_light: bool = service_data[4] & 0b00000011 != 0;
+
+            let switchbot_data = SwitchBotData::Contact {
+                battery: battery,
+                motion_detected: motion_detected,
+                door_open: door_open,
+                is_light: is_light,
+                last_opened_timeout: last_opened_timeout,
+            };
+
+            return Ok((model, switchbot_data));
+        }
+        SwitchBotDeviceModel::MotionSensor => {
+            let motion_detected: bool = service_data[1] & 0b01000000 == 0b01000000;
+            let battery: u8 = service_data[2] & 0b01111111;
+            let is_light: bool = service_data[4] & 0b00000011 != 0;
+
+            let switchbot_data = SwitchBotData::Motion {
+                battery: battery,
+                motion_detected: motion_detected,
+                is_light: is_light,
+            };
+
+            return Ok((model, switchbot_data));
         }
         _ => {
-            return (None, None)
+            return Err(DecodeError::UnsupportedModel(model));
         }
     };
 }
 
+/*
+ * Decode a Meter, MeterPlus or Hub2 reading from manufacturer data alone,
+ * for passive scans where no (or too-short) service data is available.
+ *
+ * The model can't always be told apart from manufacturer data alone, since
+ * all three share the same 8-byte manufacturer data length; if the model
+ * byte is available via a short leading service data fragment, prefer that
+ * (a leading byte that isn't a known model is rejected as
+ * `ServiceDataTooShort`), and if the service data is empty, assume Meter.
+ * That means a Hub2 advertisement scanned with no
+ * service data at all is indistinguishable from a Meter's here and will be
+ * misread as one — passive Hub2 decoding is only reliable when at least the
+ * model byte survives.
+ *
+ * Battery level isn't broadcast this way for Meter/MeterPlus, only in the
+ * active-scan service data, so it comes back as None; Hub2 never reports
+ * battery at all (see try_decode_data's Hub2 arm).
+ *
+ */
+fn try_decode_sensor_from_manufacturer_data(service_data: &[u8],
+                                            manufacturer_data: Option<&[u8]>,
+                                            manufacturer_id: Option<u16>) -> Result<(SwitchBotDeviceModel, SwitchBotData), DecodeError> {
+    let model = match service_data.first().and_then(|byte| decode_model(*byte)) {
+        Some(model @ (SwitchBotDeviceModel::Meter | SwitchBotDeviceModel::MeterPlus | SwitchBotDeviceModel::Hub2)) => model,
+        Some(model) => return Err(DecodeError::UnsupportedModel(model)),
+        None if service_data.is_empty() => SwitchBotDeviceModel::Meter,
+        None => return Err(DecodeError::ServiceDataTooShort),
+    };
+
+    check_manufacturer_id(model, manufacturer_id)?;
+
+    let Some(manufacturer_data) = manufacturer_data else {
+        return Err(DecodeError::MissingManufacturerData);
+    };
+
+    check_manufacturer_data_len(model, manufacturer_data)?;
+
+    match model {
+        SwitchBotDeviceModel::Hub2 => {
+            let switchbot_data = SwitchBotData::Hub {
+                temperature: decode_temperature(manufacturer_data[5], manufacturer_data[4]),
+                humidity: manufacturer_data[6] & 0b01111111,
+                light_level: manufacturer_data[7] & 0b00001111,
+            };
+
+            return Ok((model, switchbot_data));
+        }
+        _ => {
+            let switchbot_data = SwitchBotData::Meter {
+                temperature: decode_temperature(manufacturer_data[6], manufacturer_data[5]),
+                humidity: manufacturer_data[7] & 0b01111111,
+                battery: None,
+            };
+
+            return Ok((model, switchbot_data));
+        }
+    }
+}
+